@@ -0,0 +1,299 @@
+/// A backing data structure that can be mutated by a [`TreeCommand`].
+///
+/// Implement this for whatever model the host application uses to store its
+/// tree so that moves and open/close toggles produced by the tree view can be
+/// replayed and reversed through a [`CommandHistory`].
+pub trait TreeViewModel<NodeIdType> {
+    /// Move `id` to be a child of `new_parent` at `new_index`.
+    fn move_node(&mut self, id: NodeIdType, new_parent: Option<NodeIdType>, new_index: usize);
+    /// Set whether `id` is open or closed.
+    fn set_open(&mut self, id: NodeIdType, open: bool);
+}
+
+/// A trait object for custom, user-defined undoable actions.
+///
+/// Use this when a mutation doesn't fit [`TreeCommand::Move`] or
+/// [`TreeCommand::SetOpen`], for example renaming a node or editing its data.
+pub trait Command<Model> {
+    fn apply(&self, model: &mut Model);
+    fn undo(&self, model: &mut Model);
+}
+
+/// A single node's old and new position, as part of a
+/// [`TreeCommand::MoveMany`].
+pub struct NodeMove<NodeIdType> {
+    pub id: NodeIdType,
+    pub old_parent: Option<NodeIdType>,
+    pub old_index: usize,
+    pub new_parent: Option<NodeIdType>,
+    pub new_index: usize,
+}
+
+/// A reversible mutation produced by the tree view.
+pub enum TreeCommand<NodeIdType, Model> {
+    /// A node was moved from one parent/index to another.
+    Move {
+        id: NodeIdType,
+        old_parent: Option<NodeIdType>,
+        old_index: usize,
+        new_parent: Option<NodeIdType>,
+        new_index: usize,
+    },
+    /// Several nodes were moved together, e.g. by dragging a multi-selection.
+    ///
+    /// The moves are listed in drop order (the order the dragged rows kept
+    /// relative to each other). Applying replays them in that order; undoing
+    /// replays them in reverse so each node's `old_index` is still valid
+    /// against the positions the other moves left behind.
+    MoveMany(Vec<NodeMove<NodeIdType>>),
+    /// A node was opened or closed.
+    SetOpen {
+        id: NodeIdType,
+        was_open: bool,
+        is_open: bool,
+    },
+    /// A user-defined command.
+    Custom(Box<dyn Command<Model>>),
+}
+
+impl<NodeIdType, Model> TreeCommand<NodeIdType, Model>
+where
+    NodeIdType: Clone,
+    Model: TreeViewModel<NodeIdType>,
+{
+    /// Apply this command to `model`.
+    pub fn apply(&self, model: &mut Model) {
+        match self {
+            TreeCommand::Move {
+                id,
+                new_parent,
+                new_index,
+                ..
+            } => model.move_node(id.clone(), new_parent.clone(), *new_index),
+            TreeCommand::MoveMany(moves) => {
+                for node_move in moves {
+                    model.move_node(
+                        node_move.id.clone(),
+                        node_move.new_parent.clone(),
+                        node_move.new_index,
+                    );
+                }
+            }
+            TreeCommand::SetOpen { id, is_open, .. } => model.set_open(id.clone(), *is_open),
+            TreeCommand::Custom(command) => command.apply(model),
+        }
+    }
+
+    /// Undo this command, restoring `model` to the state it had before
+    /// [`Self::apply`] was called.
+    pub fn undo(&self, model: &mut Model) {
+        match self {
+            TreeCommand::Move {
+                id,
+                old_parent,
+                old_index,
+                ..
+            } => model.move_node(id.clone(), old_parent.clone(), *old_index),
+            TreeCommand::MoveMany(moves) => {
+                for node_move in moves.iter().rev() {
+                    model.move_node(
+                        node_move.id.clone(),
+                        node_move.old_parent.clone(),
+                        node_move.old_index,
+                    );
+                }
+            }
+            TreeCommand::SetOpen { id, was_open, .. } => model.set_open(id.clone(), *was_open),
+            TreeCommand::Custom(command) => command.undo(model),
+        }
+    }
+}
+
+/// A stack-based undo/redo history for [`TreeCommand`]s.
+///
+/// Pushing a command applies it and clears the redo stack. [`Self::undo`] and
+/// [`Self::redo`] move a command between the two stacks, undoing or
+/// reapplying it as they go.
+pub struct CommandHistory<NodeIdType, Model> {
+    undo_stack: Vec<TreeCommand<NodeIdType, Model>>,
+    redo_stack: Vec<TreeCommand<NodeIdType, Model>>,
+}
+
+impl<NodeIdType, Model> Default for CommandHistory<NodeIdType, Model> {
+    fn default() -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+}
+
+impl<NodeIdType, Model> CommandHistory<NodeIdType, Model>
+where
+    NodeIdType: Clone,
+    Model: TreeViewModel<NodeIdType>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply `command` to `model`, push it onto the undo stack, and clear the
+    /// redo stack.
+    pub fn push(&mut self, command: TreeCommand<NodeIdType, Model>, model: &mut Model) {
+        command.apply(model);
+        self.undo_stack.push(command);
+        self.redo_stack.clear();
+    }
+
+    /// Undo the most recent command, if any, moving it onto the redo stack.
+    pub fn undo(&mut self, model: &mut Model) {
+        if let Some(command) = self.undo_stack.pop() {
+            command.undo(model);
+            self.redo_stack.push(command);
+        }
+    }
+
+    /// Redo the most recently undone command, if any, moving it back onto the
+    /// undo stack.
+    pub fn redo(&mut self, model: &mut Model) {
+        if let Some(command) = self.redo_stack.pop() {
+            command.apply(model);
+            self.undo_stack.push(command);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct TestModel {
+        parent_of: std::collections::HashMap<u32, Option<u32>>,
+        index_of: std::collections::HashMap<u32, usize>,
+        open: std::collections::HashSet<u32>,
+        /// Ids passed to `move_node`, in call order.
+        moved: Vec<u32>,
+    }
+
+    impl TreeViewModel<u32> for TestModel {
+        fn move_node(&mut self, id: u32, new_parent: Option<u32>, new_index: usize) {
+            self.moved.push(id);
+            self.parent_of.insert(id, new_parent);
+            self.index_of.insert(id, new_index);
+        }
+
+        fn set_open(&mut self, id: u32, open: bool) {
+            if open {
+                self.open.insert(id);
+            } else {
+                self.open.remove(&id);
+            }
+        }
+    }
+
+    fn move_command(
+        id: u32,
+        old_parent: Option<u32>,
+        old_index: usize,
+        new_parent: Option<u32>,
+        new_index: usize,
+    ) -> TreeCommand<u32, TestModel> {
+        TreeCommand::Move {
+            id,
+            old_parent,
+            old_index,
+            new_parent,
+            new_index,
+        }
+    }
+
+    #[test]
+    fn push_applies_and_undo_reverts() {
+        let mut model = TestModel::default();
+        model.parent_of.insert(1, Some(0));
+        model.index_of.insert(1, 0);
+        let mut history = CommandHistory::new();
+
+        history.push(move_command(1, Some(0), 0, Some(2), 3), &mut model);
+        assert_eq!(model.parent_of[&1], Some(2));
+        assert_eq!(model.index_of[&1], 3);
+
+        history.undo(&mut model);
+        assert_eq!(model.parent_of[&1], Some(0));
+        assert_eq!(model.index_of[&1], 0);
+    }
+
+    #[test]
+    fn redo_reapplies_an_undone_command() {
+        let mut model = TestModel::default();
+        let mut history = CommandHistory::new();
+
+        history.push(move_command(1, None, 0, Some(2), 0), &mut model);
+        history.undo(&mut model);
+        history.redo(&mut model);
+
+        assert_eq!(model.parent_of[&1], Some(2));
+        assert!(!history.can_redo());
+        assert!(history.can_undo());
+    }
+
+    #[test]
+    fn pushing_a_command_clears_the_redo_stack() {
+        let mut model = TestModel::default();
+        let mut history = CommandHistory::new();
+
+        history.push(move_command(1, None, 0, Some(2), 0), &mut model);
+        history.undo(&mut model);
+        assert!(history.can_redo());
+
+        history.push(move_command(1, None, 0, Some(3), 0), &mut model);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn move_many_undoes_in_reverse_order_to_keep_indices_valid() {
+        let mut model = TestModel::default();
+        let mut history = CommandHistory::new();
+
+        // Two rows, 1 and 2, are dragged together from under `None` at
+        // indices 0 and 1 onto parent 9 at indices 0 and 1, in that order.
+        let command = TreeCommand::MoveMany(vec![
+            NodeMove {
+                id: 1,
+                old_parent: None,
+                old_index: 0,
+                new_parent: Some(9),
+                new_index: 0,
+            },
+            NodeMove {
+                id: 2,
+                old_parent: None,
+                old_index: 1,
+                new_parent: Some(9),
+                new_index: 1,
+            },
+        ]);
+
+        history.push(command, &mut model);
+        assert_eq!(model.parent_of[&1], Some(9));
+        assert_eq!(model.parent_of[&2], Some(9));
+        assert_eq!(model.index_of[&1], 0);
+        assert_eq!(model.index_of[&2], 1);
+
+        history.undo(&mut model);
+        assert_eq!(model.moved, vec![1, 2, 2, 1]);
+        assert_eq!(model.parent_of[&1], None);
+        assert_eq!(model.parent_of[&2], None);
+        assert_eq!(model.index_of[&1], 0);
+        assert_eq!(model.index_of[&2], 1);
+    }
+}
@@ -1,6 +1,6 @@
 use egui::{
-    epaint, vec2, CursorIcon, InnerResponse, LayerId, Order, PointerButton, Rangef, Rect, Response,
-    Sense, Shape, Stroke, Ui, Vec2,
+    epaint, vec2, CursorIcon, InnerResponse, LayerId, Order, PointerButton, Pos2, Rangef, Rect,
+    Response, Sense, Shape, Stroke, Ui, Vec2,
 };
 
 use crate::{Interaction, RowLayout, TreeViewSettings};
@@ -11,22 +11,106 @@ pub struct Row<NodeIdType> {
     pub drop_on_allowed: bool,
     pub is_open: bool,
     pub is_dir: bool,
+    /// Whether this row is the last child among its siblings. Decides
+    /// whether this row's own guide line stops at its elbow or continues
+    /// past it for further siblings below.
+    pub is_last_child: bool,
+    /// For each ancestor level (root first, not including this row's own
+    /// level), whether that ancestor still had further siblings below it.
+    /// A `false` entry means that ancestor's branch ended, so no guide line
+    /// should be drawn through its column for this row or its descendants.
+    pub ancestor_continues: Vec<bool>,
+}
+
+/// Tracks, while walking the tree in pre-order, which ancestor depths still
+/// have siblings following them — the `ancestor_continues` path to stamp
+/// onto each [`Row`] as it is visited.
+///
+/// Call [`Self::advance`] once per row, parents before children and
+/// siblings in the order they appear, e.g.:
+///
+/// ```ignore
+/// let mut path = AncestorPath::default();
+/// fn visit(node: &Node, depth: usize, is_last: bool, path: &mut AncestorPath, out: &mut Vec<Row<Id>>) {
+///     let ancestor_continues = path.advance(depth, is_last);
+///     out.push(Row { id: node.id, depth: depth as f32 * INDENT, is_last_child: is_last, ancestor_continues, .. });
+///     for (i, child) in node.children.iter().enumerate() {
+///         visit(child, depth + 1, i + 1 == node.children.len(), path, out);
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub(crate) struct AncestorPath {
+    continues: Vec<bool>,
+}
+
+impl AncestorPath {
+    /// Record a newly visited row at `depth` (0 = a root-level row) that is
+    /// or isn't `is_last_child`, returning the `ancestor_continues` path to
+    /// stamp onto that row's [`Row`].
+    pub(crate) fn advance(&mut self, depth: usize, is_last_child: bool) -> Vec<bool> {
+        self.continues.truncate(depth);
+        let path = self.continues.clone();
+        self.continues.push(!is_last_child);
+        path
+    }
+}
+
+/// The measured geometry of a single row, recorded during the layout pass so
+/// that drop decisions for the current frame can be resolved against
+/// up-to-date positions instead of rects left over from the previous frame.
+pub struct RowHitbox<NodeIdType> {
+    pub id: NodeIdType,
+    pub vertical_range: Rangef,
+    pub depth: f32,
+    pub drop_on_allowed: bool,
+}
+
+impl<NodeIdType> RowHitbox<NodeIdType>
+where
+    NodeIdType: Clone,
+{
+    fn from_row(row: &Row<NodeIdType>, rect: Rect) -> Self {
+        Self {
+            id: row.id.clone(),
+            vertical_range: rect.y_range(),
+            depth: row.depth,
+            drop_on_allowed: row.drop_on_allowed,
+        }
+    }
 }
 
 impl<NodeIdType> Row<NodeIdType>
 where
     NodeIdType: Clone + Copy + std::hash::Hash,
 {
+    /// The maximum number of selected rows that get their own stacked
+    /// preview in the drag overlay. Any rows beyond this are only reflected
+    /// in the count badge.
+    const MAX_STACKED_PREVIEWS: usize = 3;
+
+    /// The pixel offset between two consecutive stacked previews.
+    const STACK_OFFSET: Vec2 = vec2(4.0, 4.0);
+
     /// Draw the content as a drag overlay if it is beeing dragged.
+    ///
+    /// `dragged_ids` is the full dragged selection, in relative order,
+    /// *including* `self.id`; callers build it once per frame and pass the
+    /// same slice here and to [`build_move_many`] once the drop lands. When
+    /// it holds more than one id, a stack of offset preview rects is drawn
+    /// behind this row's content, with a numeric badge once the selection is
+    /// larger than what is shown.
     pub(crate) fn draw_row_dragged(
         &self,
         ui: &mut Ui,
         settings: &TreeViewSettings,
         interaction: &Interaction,
         row_response: &Response,
+        dragged_ids: &[NodeIdType],
         add_label: &mut dyn FnMut(&mut Ui),
         add_icon: &mut Option<&mut dyn FnMut(&mut Ui)>,
     ) -> bool {
+        let dragged_count = dragged_ids.len().max(1);
         //*self.drag = Some(self.id);
         ui.ctx().set_cursor_icon(CursorIcon::Alias);
 
@@ -49,9 +133,17 @@ where
         let background_rect = ui
             .child_ui(ui.available_rect_before_wrap(), *ui.layout())
             .with_layer_id(layer_id, |ui| {
+                let stacked = dragged_count
+                    .saturating_sub(1)
+                    .min(Self::MAX_STACKED_PREVIEWS - 1);
+
+                // Draw the stacked previews behind this row first, furthest
+                // offset at the back, so `self`'s content ends up on top.
+                let stack_positions = ui.painter().add(Shape::Noop);
+
                 let background_position = ui.painter().add(Shape::Noop);
 
-                let (row, _, _) = self.draw_row(ui, settings, add_label, add_icon);
+                let (row, _, _, _) = self.draw_row(ui, settings, add_label, add_icon);
 
                 ui.painter().set(
                     background_position,
@@ -62,6 +154,37 @@ where
                         Stroke::NONE,
                     ),
                 );
+
+                let mut stack_shapes = Vec::with_capacity(stacked);
+                for i in (1..=stacked).rev() {
+                    let rect = row.rect.translate(Self::STACK_OFFSET * i as f32);
+                    stack_shapes.push(epaint::RectShape::new(
+                        rect,
+                        ui.visuals().widgets.active.rounding,
+                        ui.visuals().selection.bg_fill.linear_multiply(0.4),
+                        Stroke::NONE,
+                    ));
+                }
+                ui.painter()
+                    .set(stack_positions, Shape::Vec(stack_shapes.into_iter().map(Shape::from).collect()));
+
+                if dragged_count > Self::MAX_STACKED_PREVIEWS {
+                    let badge_center = row.rect.right_top() + vec2(0.0, -2.0);
+                    let badge_radius = 9.0;
+                    ui.painter().circle_filled(
+                        badge_center,
+                        badge_radius,
+                        ui.visuals().selection.bg_fill,
+                    );
+                    ui.painter().text(
+                        badge_center,
+                        egui::Align2::CENTER_CENTER,
+                        dragged_count.to_string(),
+                        egui::FontId::monospace(badge_radius),
+                        ui.visuals().selection.stroke.color,
+                    );
+                }
+
                 row.rect
             })
             .inner;
@@ -81,7 +204,7 @@ where
         settings: &TreeViewSettings,
         add_label: &mut dyn FnMut(&mut Ui),
         add_icon: &mut Option<&mut dyn FnMut(&mut Ui)>,
-    ) -> (Response, Option<Response>, Rect) {
+    ) -> (Response, Option<Response>, Rect, RowHitbox<NodeIdType>) {
         let (reserve_closer, draw_closer, reserve_icon, draw_icon) = match settings.row_layout {
             RowLayout::Compact => (self.is_dir, self.is_dir, false, false),
             RowLayout::CompactAlignedLables => (
@@ -151,21 +274,162 @@ where
             (closer_response, label_rect_min.x)
         });
 
-        let background_rect = row_response
-            .rect
-            .expand2(vec2(0.0, ui.spacing().item_spacing.y * 0.5));
+        let background_rect = settings.row_margin.expand(row_response.rect);
+
+        if settings.show_guide_lines {
+            self.draw_guide_lines(ui, settings, background_rect);
+        }
+
         let label_rect = {
             let mut rect = background_rect.clone();
             rect.min.x = label_rect_min;
             rect
         };
 
-        (
-            row_response.with_new_rect(background_rect),
-            closer_response,
-            label_rect,
+        let row_response = row_response.with_new_rect(background_rect);
+        let hitbox = RowHitbox::from_row(self, row_response.rect);
+
+        (row_response, closer_response, label_rect, hitbox)
+    }
+
+    /// Paint vertical guide lines connecting this row to its ancestors, with
+    /// an elbow into the row's closer/icon at its own level.
+    fn draw_guide_lines(&self, ui: &Ui, settings: &TreeViewSettings, background_rect: Rect) {
+        let indent = ui.spacing().indent;
+        let levels = (self.depth / indent).round() as i32;
+        if levels <= 0 {
+            return;
+        }
+
+        let painter = ui.painter();
+        let stroke = settings.guide_line_stroke;
+        let own_level = levels - 1;
+        for level in 0..levels {
+            let x = background_rect.left() + level as f32 * indent + indent * 0.5;
+            if level == own_level {
+                // The connector into this row's own closer/icon: a tee if
+                // more siblings follow below, otherwise an elbow that stops
+                // at the row's vertical center.
+                let mid_y = background_rect.center().y;
+                let bottom_y = if self.is_last_child {
+                    mid_y
+                } else {
+                    background_rect.bottom()
+                };
+                painter.line_segment(
+                    [Pos2::new(x, background_rect.top()), Pos2::new(x, bottom_y)],
+                    stroke,
+                );
+                painter.line_segment(
+                    [Pos2::new(x, mid_y), Pos2::new(x + indent * 0.5, mid_y)],
+                    stroke,
+                );
+            } else {
+                // An ancestor's column: only draw through it if that
+                // ancestor still has siblings below, otherwise its branch
+                // already ended and there is nothing to connect to. Column
+                // `level` holds the connector of the ancestor at depth
+                // `level + 1`.
+                let continues = self
+                    .ancestor_continues
+                    .get(level as usize + 1)
+                    .copied()
+                    .unwrap_or(true);
+                if continues {
+                    painter.vline(x, background_rect.y_range(), stroke);
+                }
+            }
+        }
+    }
+
+    /// Resolve the pointer against a hitbox table collected for every
+    /// visible row this frame, and paint the drop indicator line for
+    /// whichever row and quarter it falls over. Rows whose
+    /// `drop_on_allowed` is false are skipped.
+    ///
+    /// This is the second pass of the measure-then-interact render: collect
+    /// a [`RowHitbox`] for every visible row via [`Self::draw_row`] first,
+    /// then call this once with the full table. Because `hitboxes` and
+    /// `pointer_y` are both this frame's values, the indicator reflects the
+    /// tree's current layout rather than a row rect left over from the last
+    /// frame. Returns the id of the row the pointer resolved to, if any; in
+    /// the two middle quarters that id is returned without painting a line.
+    pub(crate) fn resolve_and_paint_drop_line(
+        ui: &Ui,
+        hitboxes: &[RowHitbox<NodeIdType>],
+        x_range: Rangef,
+        pointer_y: f32,
+        hover_height: f32,
+        stroke: Stroke,
+    ) -> Option<NodeIdType> {
+        let hitbox = hitboxes
+            .iter()
+            .find(|hitbox| hitbox.drop_on_allowed && hitbox.vertical_range.contains(pointer_y))?;
+
+        let line_y = match DropQuarter::new(hitbox.vertical_range, pointer_y, hover_height)? {
+            DropQuarter::Top => hitbox.vertical_range.min,
+            DropQuarter::MiddleTop | DropQuarter::MiddleBottom => return Some(hitbox.id),
+            DropQuarter::Bottom => hitbox.vertical_range.max,
+        };
+        ui.painter().hline(x_range, line_y, stroke);
+        Some(hitbox.id)
+    }
+}
+
+/// Independent per-side margin applied to a row's background and label
+/// rects, in place of the previously hardcoded symmetric vertical padding.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RowMargin {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl RowMargin {
+    pub const ZERO: Self = Self {
+        left: 0.0,
+        right: 0.0,
+        top: 0.0,
+        bottom: 0.0,
+    };
+
+    pub fn symmetric(x: f32, y: f32) -> Self {
+        Self {
+            left: x,
+            right: x,
+            top: y,
+            bottom: y,
+        }
+    }
+
+    /// Expand `rect` outward by this margin on each side.
+    pub(crate) fn expand(&self, rect: Rect) -> Rect {
+        Rect::from_min_max(
+            Pos2::new(rect.min.x - self.left, rect.min.y - self.top),
+            Pos2::new(rect.max.x + self.right, rect.max.y + self.bottom),
         )
     }
+
+    /// Build a margin matching this crate's previous hardcoded vertical
+    /// padding: no horizontal inset, and half of `ui`'s current item spacing
+    /// above and below. [`crate::settings::TreeViewSettings::from_ui`] uses
+    /// this to seed `row_margin`. Prefer this over [`Default::default`]
+    /// wherever a `Ui` is available, since a `Ui`-less default can't read the
+    /// active theme's spacing.
+    pub fn from_ui(ui: &Ui) -> Self {
+        Self::symmetric(0.0, ui.spacing().item_spacing.y * 0.5)
+    }
+}
+
+impl Default for RowMargin {
+    /// A neutral fallback for contexts without a `Ui` to read spacing from,
+    /// e.g. building `TreeViewSettings` before the first frame. This does
+    /// *not* track the active theme's item spacing; use
+    /// [`RowMargin::from_ui`] once a `Ui` is available.
+    fn default() -> Self {
+        Self::ZERO
+    }
 }
 
 pub enum DropQuarter {
@@ -176,13 +440,14 @@ pub enum DropQuarter {
 }
 
 impl DropQuarter {
-    pub fn new(range: Rangef, cursor_pos: f32) -> Option<DropQuarter> {
-        pub const DROP_LINE_HOVER_HEIGHT: f32 = 5.0;
-
+    /// `hover_height` is how many points at the top and bottom of `range`
+    /// count as "reorder above/below" rather than "drop inside"; tune it to
+    /// match denser or sparser row layouts.
+    pub fn new(range: Rangef, cursor_pos: f32, hover_height: f32) -> Option<DropQuarter> {
         let h0 = range.min;
-        let h1 = range.min + DROP_LINE_HOVER_HEIGHT;
+        let h1 = range.min + hover_height;
         let h2 = (range.min + range.max) / 2.0;
-        let h3 = range.max - DROP_LINE_HOVER_HEIGHT;
+        let h3 = range.max - hover_height;
         let h4 = range.max;
 
         match cursor_pos {
@@ -194,3 +459,145 @@ impl DropQuarter {
         }
     }
 }
+
+/// Build the [`TreeCommand::MoveMany`] produced by dropping the full
+/// selection (`dragged_ids`, in their relative order) onto `new_parent`
+/// starting at `new_index`, so the drop moves the whole set rather than just
+/// the row the pointer happened to land on.
+///
+/// `old_position` looks up each dragged id's current parent and index so the
+/// move can later be undone through a [`crate::command::CommandHistory`].
+pub(crate) fn build_move_many<NodeIdType, Model>(
+    dragged_ids: &[NodeIdType],
+    new_parent: Option<NodeIdType>,
+    new_index: usize,
+    mut old_position: impl FnMut(&NodeIdType) -> (Option<NodeIdType>, usize),
+) -> crate::command::TreeCommand<NodeIdType, Model>
+where
+    NodeIdType: Clone,
+{
+    let moves = dragged_ids
+        .iter()
+        .enumerate()
+        .map(|(offset, id)| {
+            let (old_parent, old_index) = old_position(id);
+            crate::command::NodeMove {
+                id: id.clone(),
+                old_parent,
+                old_index,
+                new_parent: new_parent.clone(),
+                new_index: new_index + offset,
+            }
+        })
+        .collect();
+    crate::command::TreeCommand::MoveMany(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // root
+    // ├─ A
+    // └─ B (last child of root)
+    //    └─ C
+    #[test]
+    fn ancestor_path_stops_at_a_last_childs_subtree() {
+        let mut path = AncestorPath::default();
+
+        let a = path.advance(0, false);
+        assert_eq!(a, Vec::<bool>::new());
+
+        let b = path.advance(0, true);
+        assert_eq!(b, Vec::<bool>::new());
+
+        // C is B's child: B has no siblings below it, so C's path must record
+        // B's level as ended rather than as "continues".
+        let c = path.advance(1, true);
+        assert_eq!(c, vec![false]);
+    }
+
+    #[test]
+    fn ancestor_path_keeps_continuing_branches_alive_for_descendants() {
+        let mut path = AncestorPath::default();
+
+        // A is not root's last child, so its descendants should see `true`
+        // for A's level.
+        let a = path.advance(0, false);
+        assert_eq!(a, Vec::<bool>::new());
+        let a_child = path.advance(1, true);
+        assert_eq!(a_child, vec![true]);
+    }
+
+    #[test]
+    fn drop_quarter_splits_the_range_into_four_bands() {
+        let range = Rangef::new(0.0, 20.0);
+
+        assert!(matches!(
+            DropQuarter::new(range, 0.0, 5.0),
+            Some(DropQuarter::Top)
+        ));
+        assert!(matches!(
+            DropQuarter::new(range, 7.0, 5.0),
+            Some(DropQuarter::MiddleTop)
+        ));
+        assert!(matches!(
+            DropQuarter::new(range, 13.0, 5.0),
+            Some(DropQuarter::MiddleBottom)
+        ));
+        assert!(matches!(
+            DropQuarter::new(range, 18.0, 5.0),
+            Some(DropQuarter::Bottom)
+        ));
+    }
+
+    #[test]
+    fn drop_quarter_shrinks_the_hover_bands_with_a_smaller_hover_height() {
+        let range = Rangef::new(0.0, 20.0);
+
+        // 3.0 is in the top reorder band with a 5pt hover height, but with a
+        // 1pt hover height it falls in the (widened) middle band instead.
+        assert!(matches!(
+            DropQuarter::new(range, 3.0, 5.0),
+            Some(DropQuarter::Top)
+        ));
+        assert!(matches!(
+            DropQuarter::new(range, 3.0, 1.0),
+            Some(DropQuarter::MiddleTop)
+        ));
+    }
+
+    #[test]
+    fn row_margin_expand_grows_the_rect_on_each_side() {
+        let margin = RowMargin {
+            left: 1.0,
+            right: 2.0,
+            top: 3.0,
+            bottom: 4.0,
+        };
+        let rect = Rect::from_min_max(Pos2::new(10.0, 10.0), Pos2::new(20.0, 20.0));
+
+        let expanded = margin.expand(rect);
+
+        assert_eq!(expanded.min, Pos2::new(9.0, 7.0));
+        assert_eq!(expanded.max, Pos2::new(22.0, 24.0));
+    }
+
+    #[test]
+    fn build_move_many_offsets_new_index_by_position_in_the_dragged_set() {
+        let dragged_ids = [1u32, 2, 3];
+
+        let command: crate::command::TreeCommand<u32, ()> =
+            build_move_many(&dragged_ids, Some(9), 5, |id| (None, *id as usize));
+
+        let crate::command::TreeCommand::MoveMany(moves) = command else {
+            panic!("expected a MoveMany command");
+        };
+        assert_eq!(moves.len(), 3);
+        for (offset, node_move) in moves.iter().enumerate() {
+            assert_eq!(node_move.new_parent, Some(9));
+            assert_eq!(node_move.new_index, 5 + offset);
+            assert_eq!(node_move.old_index, dragged_ids[offset] as usize);
+        }
+    }
+}
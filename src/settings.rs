@@ -0,0 +1,64 @@
+use egui::{Response, Stroke, Ui};
+
+use crate::row::RowMargin;
+
+/// How a row's closer, icon, and label are laid out relative to each other.
+pub enum RowLayout {
+    /// Only directories reserve space for a closer; no icons are drawn.
+    Compact,
+    /// Like [`Self::Compact`], but labels are aligned regardless of whether
+    /// a row is a directory.
+    CompactAlignedLables,
+    /// Every row reserves space for a closer, and rows drawn with an icon
+    /// also reserve space for it, aligning icons across directories and
+    /// leaves. Rows without an icon do not reserve icon space.
+    AlignedIcons,
+    /// Every row reserves space for a closer, an icon, and an aligned label.
+    AlignedIconsAndLabels,
+}
+
+/// Configuration for a tree view, read once per row while drawing.
+pub struct TreeViewSettings {
+    pub row_layout: RowLayout,
+    /// Margin applied to each row's background and label rects.
+    pub row_margin: RowMargin,
+    pub show_guide_lines: bool,
+    pub guide_line_stroke: Stroke,
+}
+
+impl TreeViewSettings {
+    /// Build the settings this crate used before they were configurable:
+    /// [`RowLayout::Compact`], no guide lines, and [`RowMargin::from_ui`] so
+    /// the row padding matches the active theme's item spacing.
+    pub fn from_ui(ui: &Ui) -> Self {
+        Self {
+            row_layout: RowLayout::Compact,
+            row_margin: RowMargin::from_ui(ui),
+            show_guide_lines: false,
+            guide_line_stroke: ui.visuals().widgets.noninteractive.bg_stroke,
+        }
+    }
+}
+
+/// The pointer interaction driving the current frame's row, e.g. a drag in
+/// progress.
+pub struct Interaction {
+    pub response: Response,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_ui_keeps_the_previous_hardcoded_padding() {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let settings = TreeViewSettings::from_ui(ui);
+                let expected = RowMargin::symmetric(0.0, ui.spacing().item_spacing.y * 0.5);
+                assert_eq!(settings.row_margin, expected);
+            });
+        });
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use egui::{Pos2, Vec2};
+
+/// The persistent part of a tree view's state: which nodes are open, what is
+/// selected, and where the view is scrolled to.
+///
+/// Behind the `serde` feature this round-trips through `serde`, so a host
+/// application can save it alongside the rest of its UI layout and restore it
+/// on the next launch with [`TreeViewState::load`] / [`TreeViewState::dump`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeViewState<NodeIdType>
+where
+    NodeIdType: Clone + Eq + std::hash::Hash,
+{
+    pub(crate) open: HashSet<NodeIdType>,
+    pub(crate) selected: HashSet<NodeIdType>,
+    pub(crate) scroll_offset: Vec2,
+    pub(crate) drag_source: Option<NodeIdType>,
+    pub(crate) drag_offset: Option<Pos2>,
+}
+
+// Hand-written instead of `#[derive(Default)]`: the derive would add a
+// `NodeIdType: Default` bound, but none of the fields actually need one.
+impl<NodeIdType> Default for TreeViewState<NodeIdType>
+where
+    NodeIdType: Clone + Eq + std::hash::Hash,
+{
+    fn default() -> Self {
+        Self {
+            open: HashSet::new(),
+            selected: HashSet::new(),
+            scroll_offset: Vec2::ZERO,
+            drag_source: None,
+            drag_offset: None,
+        }
+    }
+}
+
+impl<NodeIdType> TreeViewState<NodeIdType>
+where
+    NodeIdType: Clone + Eq + std::hash::Hash,
+{
+    pub fn is_open(&self, id: &NodeIdType) -> bool {
+        self.open.contains(id)
+    }
+
+    pub fn set_open(&mut self, id: NodeIdType, is_open: bool) {
+        if is_open {
+            self.open.insert(id);
+        } else {
+            self.open.remove(&id);
+        }
+    }
+
+    pub fn is_selected(&self, id: &NodeIdType) -> bool {
+        self.selected.contains(id)
+    }
+
+    pub fn selected(&self) -> impl Iterator<Item = &NodeIdType> {
+        self.selected.iter()
+    }
+
+    pub fn set_selected(&mut self, selected: HashSet<NodeIdType>) {
+        self.selected = selected;
+    }
+
+    pub fn scroll_offset(&self) -> Vec2 {
+        self.scroll_offset
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<NodeIdType> TreeViewState<NodeIdType>
+where
+    NodeIdType: Clone + Eq + std::hash::Hash,
+{
+    /// Load a previously dumped state for the tree view with the given `id`,
+    /// if any was stored.
+    ///
+    /// Called by [`crate::tree_view::TreeView::load_state`]; use that instead
+    /// when you already have the `TreeView`.
+    pub fn load(ui: &egui::Ui, id: egui::Id) -> Option<Self>
+    where
+        NodeIdType: serde::de::DeserializeOwned + 'static,
+    {
+        ui.data(|data| data.get_persisted(id))
+    }
+
+    /// Persist this state for the tree view with the given `id` so it can be
+    /// restored with [`Self::load`] in a later session.
+    ///
+    /// Called by [`crate::tree_view::TreeView::dump_state`]; use that instead
+    /// when you already have the `TreeView`.
+    pub fn dump(&self, ui: &egui::Ui, id: egui::Id)
+    where
+        NodeIdType: serde::Serialize + 'static,
+    {
+        ui.data_mut(|data| data.insert_persisted(id, self.clone()));
+    }
+}
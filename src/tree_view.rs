@@ -0,0 +1,50 @@
+use std::marker::PhantomData;
+
+use egui::{Id, Ui};
+
+use crate::state::TreeViewState;
+
+/// A tree view widget, identified by an [`Id`] so its [`TreeViewState`] can be
+/// looked up in egui's temporary/persisted data and, behind the `serde`
+/// feature, saved across sessions.
+pub struct TreeView<NodeIdType> {
+    id: Id,
+    _node_id: PhantomData<NodeIdType>,
+}
+
+impl<NodeIdType> TreeView<NodeIdType> {
+    pub fn new(id_source: impl std::hash::Hash) -> Self {
+        Self {
+            id: Id::new(id_source),
+            _node_id: PhantomData,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<NodeIdType> TreeView<NodeIdType>
+where
+    NodeIdType: Clone + Eq + std::hash::Hash,
+{
+    /// Load this tree view's previously dumped [`TreeViewState`], if any was
+    /// stored for [`Self::id`].
+    pub fn load_state(&self, ui: &Ui) -> Option<TreeViewState<NodeIdType>>
+    where
+        NodeIdType: serde::de::DeserializeOwned + 'static,
+    {
+        TreeViewState::load(ui, self.id)
+    }
+
+    /// Persist `state` for this tree view so it can be restored with
+    /// [`Self::load_state`] in a later session.
+    pub fn dump_state(&self, ui: &Ui, state: &TreeViewState<NodeIdType>)
+    where
+        NodeIdType: serde::Serialize + 'static,
+    {
+        state.dump(ui, self.id);
+    }
+}